@@ -2,14 +2,17 @@
 
 use std::borrow::Borrow;
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use std::{mem, thread, u64};
 
+use crossbeam::channel::{self, Sender};
 use futures::future;
 use keys::{Key, Value};
 use kvproto::kvrpcpb::{CommandPri, Context, LockInfo};
 
-use crate::storage::kv::with_tls_engine;
+use crate::storage::kv::{destroy_tls_engine, set_tls_engine, with_tls_engine};
 use crate::storage::kv::{CbContext, Modify, Result as EngineResult};
 use crate::storage::lock_manager::{self, Lock, LockManager};
 use crate::storage::mvcc::{
@@ -63,18 +66,203 @@ impl Task {
         self.cmd.priority()
     }
 
+    pub fn readonly(&self) -> bool {
+        self.cmd.readonly()
+    }
+
     pub fn context(&self) -> &Context {
         &self.cmd.ctx
     }
+
+    fn is_high_priority(&self) -> bool {
+        self.cmd.priority() == CommandPri::High
+    }
+
+    // A command is heavy when it scans or sweeps over a large key set and may
+    // block on the engine: a `Prewrite` of more than `FORWARD_MIN_MUTATIONS_NUM`
+    // mutations (an optimistic one also runs `has_data_in_range`), a
+    // `ResolveLock` sweep over up to `RESOLVE_LOCK_BATCH_SIZE` keys, or a
+    // `ResolveLockLite` over all of its resolve keys.
+    fn is_heavy(&self) -> bool {
+        match self.cmd.kind {
+            CommandKind::Prewrite { ref mutations, .. } => {
+                mutations.len() > FORWARD_MIN_MUTATIONS_NUM
+            }
+            CommandKind::ResolveLock { .. } | CommandKind::ResolveLockLite { .. } => true,
+            _ => false,
+        }
+    }
 }
 
+/// Default capacity of the scheduler's bounded message channel.
+///
+/// Worker completion messages (`WriteFinished`/`ReadFinished`/`FinishedWithErr`)
+/// are buffered in a queue of this size; it is overridable through the scheduler
+/// config so operators can tune fan-in buffering against memory.
+pub const DEFAULT_SCHED_MSG_CHANNEL_CAPACITY: usize = 40960;
+
 pub trait MsgScheduler: Clone + Send + 'static {
-    fn on_msg(&self, task: Msg);
+    /// Delivers a completion message to the scheduler.
+    fn on_msg(&self, msg: Msg);
+}
+
+/// A bounded, backpressured `MsgScheduler` wrapper around an inner scheduler.
+///
+/// `on_msg` sends completion messages into a bounded MPMC queue of `capacity`
+/// slots, drained by a dedicated forwarder thread that calls the inner
+/// scheduler. When the inner scheduler can not keep up the queue fills and
+/// `on_msg` blocks the producing worker until a slot frees, so backpressure
+/// propagates up through `notify_scheduler` into the pool workers running
+/// `process_read`/`process_write` instead of
+/// letting in-flight completions grow without bound. `depth` tracks the current
+/// queue occupancy so operators can observe scheduler saturation.
+pub struct BoundedMsgScheduler {
+    sender: Sender<Msg>,
+    depth: Arc<AtomicUsize>,
+}
+
+impl Clone for BoundedMsgScheduler {
+    fn clone(&self) -> BoundedMsgScheduler {
+        BoundedMsgScheduler {
+            sender: self.sender.clone(),
+            depth: self.depth.clone(),
+        }
+    }
+}
+
+impl BoundedMsgScheduler {
+    /// Wraps `inner` with a queue of `DEFAULT_SCHED_MSG_CHANNEL_CAPACITY` slots.
+    pub fn new<S: MsgScheduler>(inner: S) -> BoundedMsgScheduler {
+        Self::with_capacity(inner, DEFAULT_SCHED_MSG_CHANNEL_CAPACITY)
+    }
+
+    /// Wraps `inner` with a queue of `capacity` slots.
+    pub fn with_capacity<S: MsgScheduler>(inner: S, capacity: usize) -> BoundedMsgScheduler {
+        let (sender, receiver) = channel::bounded::<Msg>(capacity);
+        let depth = Arc::new(AtomicUsize::new(0));
+        let forwarder_depth = depth.clone();
+        thread::Builder::new()
+            .name("sched-msg-forwarder".to_owned())
+            .spawn(move || {
+                for msg in receiver.iter() {
+                    forwarder_depth.fetch_sub(1, Ordering::SeqCst);
+                    inner.on_msg(msg);
+                }
+            })
+            .unwrap();
+        BoundedMsgScheduler { sender, depth }
+    }
+
+    /// Current number of messages queued but not yet handed to the inner scheduler.
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::SeqCst)
+    }
+}
+
+impl MsgScheduler for BoundedMsgScheduler {
+    fn on_msg(&self, msg: Msg) {
+        self.depth.fetch_add(1, Ordering::SeqCst);
+        // A bounded send blocks the producing worker while the queue is full,
+        // which is the backpressure we want. A disconnected channel only happens
+        // once the forwarder thread has exited; the message is then dropped.
+        if self.sender.send(msg).is_err() {
+            self.depth.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// `CommandRuntime` abstracts *how* a command body is scheduled to run, mirroring
+/// the way libstd hides its M:N and 1:1 runtimes behind a single `Runtime` trait.
+/// Light, readonly commands run on a cooperative future pool, while heavy or
+/// blocking ones are handed to a dedicated native-thread pool, so that a long
+/// write sweep can not starve tail-latency-sensitive point reads.
+pub trait CommandRuntime: Clone + Send + 'static {
+    /// Spawns a command body that cooperates with the future runtime.
+    fn spawn<F: FnOnce() + Send + 'static>(&self, f: F);
+
+    /// Spawns a command body that may block on the engine.
+    fn spawn_blocking<F: FnOnce() + Send + 'static>(&self, f: F);
+}
+
+impl CommandRuntime for SchedPool {
+    fn spawn<F: FnOnce() + Send + 'static>(&self, f: F) {
+        self.pool
+            .spawn(move || {
+                f();
+                future::ok::<_, ()>(())
+            })
+            .unwrap();
+    }
+
+    // The cooperative pool has no separate blocking executor; a closure that
+    // blocks simply holds its worker for the duration. Heavy/blocking commands
+    // are instead routed to `BlockingPool`, which is the real blocking runtime.
+    fn spawn_blocking<F: FnOnce() + Send + 'static>(&self, f: F) {
+        self.spawn(f);
+    }
+}
+
+/// A dedicated 1:1 native-thread pool for heavy or blocking command bodies.
+///
+/// Each job runs to completion on one of the pool's `threads` dedicated OS
+/// threads, which share a single job queue, so a command that
+/// blocks on the engine — a large optimistic `Prewrite` scan or a `ResolveLock`
+/// sweep over `RESOLVE_LOCK_BATCH_SIZE` keys — never occupies a worker that
+/// serves tail-latency-sensitive point reads on the cooperative `SchedPool`.
+#[derive(Clone)]
+pub struct BlockingPool {
+    sender: Sender<Box<dyn FnOnce() + Send + 'static>>,
+}
+
+impl BlockingPool {
+    pub fn new<E: Engine>(engine: E, name: &str, threads: usize) -> BlockingPool {
+        let (sender, receiver) = channel::unbounded::<Box<dyn FnOnce() + Send + 'static>>();
+        for i in 0..threads {
+            let receiver = receiver.clone();
+            let engine = engine.clone();
+            thread::Builder::new()
+                .name(format!("{}-{}", name, i))
+                .spawn(move || {
+                    // Mirror SchedPool's after_start/before_stop hooks so that heavy
+                    // writes dispatched here find the thread-local engine installed
+                    // before they call `with_tls_engine` in `process_write`.
+                    set_tls_engine(engine);
+                    for job in receiver.iter() {
+                        job();
+                    }
+                    // Safety: this thread installed the engine above and runs no more
+                    // jobs once the job channel is disconnected at shutdown.
+                    unsafe {
+                        destroy_tls_engine::<E>();
+                    }
+                })
+                .unwrap();
+        }
+        BlockingPool { sender }
+    }
+}
+
+impl CommandRuntime for BlockingPool {
+    fn spawn<F: FnOnce() + Send + 'static>(&self, f: F) {
+        // A disconnected channel only happens once every worker thread has
+        // exited; the job is then dropped without running.
+        let _ = self.sender.send(Box::new(f));
+    }
+
+    // Every job already runs on a dedicated native thread of this pool, so
+    // blocking work needs no special handling beyond being enqueued here.
+    fn spawn_blocking<F: FnOnce() + Send + 'static>(&self, f: F) {
+        self.spawn(f);
+    }
 }
 
 pub struct Executor<E: Engine, S: MsgScheduler, L: LockManager> {
-    // We put time consuming tasks to the thread pool.
+    // The cooperative M:N pool that runs light commands, readonly or not, that
+    // are neither heavy nor high-priority.
     sched_pool: Option<SchedPool>,
+    // The dedicated native-thread pool that runs heavy or blocking commands, so
+    // long write sweeps do not contend with latency-sensitive reads.
+    blocking_pool: Option<BlockingPool>,
     // And the tasks completes we post a completion to the `Scheduler`.
     scheduler: Option<S>,
     // If the task releases some locks, we wake up waiters waiting for them.
@@ -84,9 +272,15 @@ pub struct Executor<E: Engine, S: MsgScheduler, L: LockManager> {
 }
 
 impl<E: Engine, S: MsgScheduler, L: LockManager> Executor<E, S, L> {
-    pub fn new(scheduler: S, pool: SchedPool, lock_mgr: Option<L>) -> Self {
+    pub fn new(
+        scheduler: S,
+        pool: SchedPool,
+        blocking_pool: BlockingPool,
+        lock_mgr: Option<L>,
+    ) -> Self {
         Executor {
             sched_pool: Some(pool),
+            blocking_pool: Some(blocking_pool),
             scheduler: Some(scheduler),
             lock_mgr,
             _phantom: Default::default(),
@@ -101,6 +295,10 @@ impl<E: Engine, S: MsgScheduler, L: LockManager> Executor<E, S, L> {
         self.sched_pool.clone().unwrap()
     }
 
+    fn clone_blocking_pool(&mut self) -> BlockingPool {
+        self.blocking_pool.clone().unwrap()
+    }
+
     fn take_scheduler(&mut self) -> S {
         self.scheduler.take().unwrap()
     }
@@ -155,38 +353,44 @@ impl<E: Engine, S: MsgScheduler, L: LockManager> Executor<E, S, L> {
         if let Some(term) = cb_ctx.term {
             task.cmd.ctx.set_term(term);
         }
+        let readonly = task.readonly();
+        // Heavy sweeps/scans and high-priority commands go to the native-thread
+        // pool so they can block on the engine without starving point reads.
+        let use_blocking_pool = task.is_high_priority() || task.is_heavy();
         let sched_pool = self.clone_pool();
-        let readonly = task.cmd.readonly();
-        sched_pool
-            .pool
-            .spawn(move || {
-                fail_point!("scheduler_async_snapshot_finish");
+        let blocking_pool = self.clone_blocking_pool();
+        let work = move || {
+            fail_point!("scheduler_async_snapshot_finish");
 
-                let read_duration = Instant::now_coarse();
+            let read_duration = Instant::now_coarse();
 
-                let region_id = task.region_id;
-                let ts = task.ts;
-                let timer = SlowTimer::new();
+            let region_id = task.region_id;
+            let ts = task.ts;
+            let timer = SlowTimer::new();
 
-                let statistics = if readonly {
-                    self.process_read(snapshot, task)
-                } else {
-                    // Safety: `self.sched_pool` ensures a TLS engine exists.
-                    unsafe { with_tls_engine(|engine| self.process_write(engine, snapshot, task)) }
-                };
-                tls_collect_scan_details(tag.get_str(), &statistics);
-                slow_log!(
-                    timer,
-                    "[region {}] scheduler handle command: {}, ts: {}",
-                    region_id,
-                    tag,
-                    ts
-                );
-
-                tls_collect_read_duration(tag.get_str(), read_duration.elapsed());
-                future::ok::<_, ()>(())
-            })
-            .unwrap();
+            let statistics = if readonly {
+                self.process_read(snapshot, task)
+            } else {
+                // Safety: both `SchedPool` and `BlockingPool` workers install a
+                // TLS engine, so one exists on whichever pool runs this.
+                unsafe { with_tls_engine(|engine| self.process_write(engine, snapshot, task)) }
+            };
+            tls_collect_scan_details(tag.get_str(), &statistics);
+            slow_log!(
+                timer,
+                "[region {}] scheduler handle command: {}, ts: {}",
+                region_id,
+                tag,
+                ts
+            );
+
+            tls_collect_read_duration(tag.get_str(), read_duration.elapsed());
+        };
+        if use_blocking_pool {
+            blocking_pool.spawn_blocking(work);
+        } else {
+            sched_pool.spawn(work);
+        }
     }
 
     /// Processes a read command within a worker thread, then posts `ReadFinished` message back to the
@@ -548,11 +752,54 @@ fn process_write_impl<S: Snapshot, L: LockManager>(
 
             statistics.add(&txn.take_statistics());
             if locks.is_empty() {
-                let pr = ProcessResult::MultiRes { results: vec![] };
-                let modifies = txn.into_modifies();
-                (pr, modifies, rows, cmd.ctx, None)
+                if options.try_one_pc {
+                    // All keys fall in a single region and prewrote cleanly, so commit
+                    // directly in one round trip. `try_one_pc` makes prewrite derive a
+                    // per-key `min_commit_ts` from the max ts observed while scanning
+                    // (the same machinery async commit uses), so the largest such value
+                    // is strictly greater than any version a reader could already have
+                    // seen and is therefore a safe commit_ts. `one_pc_commit` turns the
+                    // prewritten keys into `Write` records directly, leaving no lock.
+                    let commit_ts = txn.min_commit_ts();
+                    if commit_ts <= start_ts {
+                        // `min_commit_ts` was not derived from the max observed ts;
+                        // refuse the 1PC attempt so the client retries with 2PC rather
+                        // than committing at a ts a reader could already have passed.
+                        return Err(Error::from(ErrorInner::InvalidTxnTso {
+                            start_ts,
+                            commit_ts,
+                        }));
+                    }
+                    txn.one_pc_commit(commit_ts)?;
+                    statistics.add(&txn.take_statistics());
+                    let pr = ProcessResult::TxnStatus {
+                        txn_status: TxnStatus::committed(commit_ts),
+                    };
+                    (pr, txn.into_modifies(), rows, cmd.ctx, None)
+                } else if options.async_commit {
+                    // Async commit: the primary lock now records the secondary keys and
+                    // each lock carries its own min_commit_ts (computed per key during
+                    // prewrite). Return the largest min_commit_ts so the client can
+                    // commit without fetching a second timestamp from PD.
+                    let min_commit_ts = txn.min_commit_ts();
+                    let secondaries = options.secondary_keys.clone().unwrap_or_default();
+                    let pr = ProcessResult::TxnStatus {
+                        txn_status: TxnStatus::uncommitted(
+                            options.lock_ttl,
+                            min_commit_ts,
+                            secondaries,
+                        ),
+                    };
+                    (pr, txn.into_modifies(), rows, cmd.ctx, None)
+                } else {
+                    let pr = ProcessResult::MultiRes { results: vec![] };
+                    let modifies = txn.into_modifies();
+                    (pr, modifies, rows, cmd.ctx, None)
+                }
             } else {
-                // Skip write stage if some keys are locked.
+                // Some keys are locked. For a 1PC attempt this is a conflict and the
+                // client must retry with the normal 2PC path; either way skip the
+                // write stage and report the locks.
                 let pr = ProcessResult::MultiRes { results: locks };
                 (pr, vec![], 0, cmd.ctx, None)
             }
@@ -830,7 +1077,8 @@ fn process_write_impl<S: Snapshot, L: LockManager>(
 
             statistics.add(&txn.take_statistics());
             let pr = ProcessResult::TxnStatus {
-                txn_status: TxnStatus::uncommitted(lock_ttl, TimeStamp::zero()),
+                // A heartbeat only refreshes the TTL and never carries secondaries.
+                txn_status: TxnStatus::uncommitted(lock_ttl, TimeStamp::zero(), vec![]),
             };
             (pr, txn.into_modifies(), 1, cmd.ctx, None)
         }
@@ -849,8 +1097,12 @@ fn process_write_impl<S: Snapshot, L: LockManager>(
                 rollback_if_not_exist,
             )?;
 
-            // The lock is possibly resolved here only when the `check_txn_status` cleaned up the
-            // lock, and this may happen only when it returns `TtlExpire` or `LockNotExist`.
+            // The lock is possibly resolved here only when `check_txn_status` cleaned
+            // up the lock, and this may happen only when it returns `TtlExpire` or
+            // `LockNotExist`. An unexpired async-commit lock comes back as
+            // `Uncommitted` (carrying its `min_commit_ts` and secondary list, which
+            // we forward untouched in `pr` below); that variant leaves any waiters
+            // alone so a conflicting reader can push the commit_ts forward.
             match txn_status {
                 TxnStatus::TtlExpire | TxnStatus::LockNotExist => {
                     let key_hashes = gen_key_hashes_if_needed(&lock_mgr, &[&primary_key]);
@@ -887,6 +1139,9 @@ fn process_write_impl<S: Snapshot, L: LockManager>(
     })
 }
 
+// Hands a completion message back to the scheduler. When the scheduler is a
+// `BoundedMsgScheduler` this blocks the worker while its queue is full,
+// backpressuring the process pipeline.
 pub fn notify_scheduler<S: MsgScheduler>(scheduler: S, msg: Msg) {
     scheduler.on_msg(msg);
 }
@@ -1113,4 +1368,289 @@ mod tests {
         engine.write(&ctx, ret.to_be_write).unwrap();
         Ok(())
     }
+
+    fn mvcc_by_key<E: Engine>(engine: &E, key: Key) -> MvccInfo {
+        let ctx = Context::default();
+        let snap = engine.snapshot(&ctx).unwrap();
+        let cmd = Command {
+            ctx,
+            kind: CommandKind::MvccByKey { key },
+        };
+        let mut statistics = Statistics::default();
+        match process_read_impl::<E>(cmd, snap, &mut statistics).unwrap() {
+            ProcessResult::MvccKey { mvcc } => mvcc,
+            _ => panic!("expect an MvccKey result"),
+        }
+    }
+
+    #[test]
+    fn test_mvcc_by_key_history_is_complete_and_ordered() {
+        let engine = TestEngineBuilder::new().build().unwrap();
+        let mut statistics = Statistics::default();
+        let raw = b"k".to_vec();
+        let key = Key::from_raw(&raw);
+        // A long value so the payload lands in CF_DEFAULT and shows up in `values`.
+        let value = vec![b'v'; 128];
+
+        for &(start_ts, commit_ts) in &[(10u64, 15u64), (20, 25), (30, 35)] {
+            prewrite(
+                &engine,
+                &mut statistics,
+                vec![Mutation::Put((key.clone(), value.clone()))],
+                raw.clone(),
+                start_ts,
+            )
+            .unwrap();
+            commit(
+                &engine,
+                &mut statistics,
+                vec![key.clone()],
+                start_ts,
+                commit_ts,
+            )
+            .unwrap();
+        }
+
+        let mvcc = mvcc_by_key(&engine, key);
+        // No lock remains after the committed versions.
+        assert!(mvcc.lock.is_none());
+        // Every committed version is present, ordered newest-first.
+        let commit_tses: Vec<TimeStamp> = mvcc.writes.iter().map(|(ts, _)| *ts).collect();
+        assert_eq!(
+            commit_tses,
+            vec![35.into(), 25.into(), 15.into()],
+            "writes must be ordered newest-first"
+        );
+        // The underlying values are surfaced too.
+        assert_eq!(mvcc.values.len(), 3);
+        let value_tses: Vec<TimeStamp> = mvcc.values.iter().map(|(ts, _)| *ts).collect();
+        assert_eq!(value_tses, vec![30.into(), 20.into(), 10.into()]);
+    }
+
+    fn mvcc_by_start_ts<E: Engine>(engine: &E, start_ts: u64) -> Option<(Key, MvccInfo)> {
+        let ctx = Context::default();
+        let snap = engine.snapshot(&ctx).unwrap();
+        let cmd = Command {
+            ctx,
+            kind: CommandKind::MvccByStartTs {
+                start_ts: TimeStamp::from(start_ts),
+            },
+        };
+        let mut statistics = Statistics::default();
+        match process_read_impl::<E>(cmd, snap, &mut statistics).unwrap() {
+            ProcessResult::MvccStartTs { mvcc } => mvcc,
+            _ => panic!("expect an MvccStartTs result"),
+        }
+    }
+
+    #[test]
+    fn test_mvcc_by_start_ts_finds_locked_key() {
+        let engine = TestEngineBuilder::new().build().unwrap();
+        let mut statistics = Statistics::default();
+        let raw = b"k".to_vec();
+        let key = Key::from_raw(&raw);
+        let value = vec![b'v'; 128];
+
+        // Commit one version, then leave a lock from a later, uncommitted txn.
+        prewrite(
+            &engine,
+            &mut statistics,
+            vec![Mutation::Put((key.clone(), value.clone()))],
+            raw.clone(),
+            10,
+        )
+        .unwrap();
+        commit(&engine, &mut statistics, vec![key.clone()], 10, 15).unwrap();
+        prewrite(
+            &engine,
+            &mut statistics,
+            vec![Mutation::Put((key.clone(), value))],
+            raw.clone(),
+            50,
+        )
+        .unwrap();
+
+        // `MvccByStartTs` scans locks to locate the key touched by txn 50.
+        let (found_key, mvcc) = mvcc_by_start_ts(&engine, 50).unwrap();
+        assert_eq!(found_key, key);
+        // The uncommitted lock is surfaced...
+        assert!(mvcc.lock.is_some());
+        // ...alongside the committed history for that key.
+        let commit_tses: Vec<TimeStamp> = mvcc.writes.iter().map(|(ts, _)| *ts).collect();
+        assert_eq!(commit_tses, vec![15.into()]);
+
+        // A start_ts with no matching lock yields nothing.
+        assert!(mvcc_by_start_ts(&engine, 99).is_none());
+    }
+
+    fn one_pc_prewrite<E: Engine>(
+        engine: &E,
+        statistics: &mut Statistics,
+        mutations: Vec<Mutation>,
+        primary: Vec<u8>,
+        start_ts: u64,
+    ) -> Result<TimeStamp> {
+        let ctx = Context::default();
+        let snap = engine.snapshot(&ctx)?;
+        let mut options = Options::default();
+        options.try_one_pc = true;
+        let cmd = Command {
+            ctx,
+            kind: CommandKind::Prewrite {
+                mutations,
+                primary,
+                start_ts: TimeStamp::from(start_ts),
+                options,
+            },
+        };
+        let m = DummyLockManager {};
+        let ret = process_write_impl(cmd, snap, Some(m), statistics)?;
+        let commit_ts = match ret.pr {
+            ProcessResult::TxnStatus {
+                txn_status: TxnStatus::Committed { commit_ts },
+            } => commit_ts,
+            _ => panic!("expect a committed 1PC result"),
+        };
+        let ctx = Context::default();
+        engine.write(&ctx, ret.to_be_write).unwrap();
+        Ok(commit_ts)
+    }
+
+    fn async_commit_prewrite<E: Engine>(
+        engine: &E,
+        statistics: &mut Statistics,
+        mutations: Vec<Mutation>,
+        primary: Vec<u8>,
+        secondaries: Vec<Vec<u8>>,
+        start_ts: u64,
+    ) -> Result<TxnStatus> {
+        let ctx = Context::default();
+        let snap = engine.snapshot(&ctx)?;
+        let mut options = Options::default();
+        options.async_commit = true;
+        options.secondary_keys = Some(secondaries);
+        let cmd = Command {
+            ctx,
+            kind: CommandKind::Prewrite {
+                mutations,
+                primary,
+                start_ts: TimeStamp::from(start_ts),
+                options,
+            },
+        };
+        let m = DummyLockManager {};
+        let ret = process_write_impl(cmd, snap, Some(m), statistics)?;
+        let txn_status = match ret.pr {
+            ProcessResult::TxnStatus { txn_status } => txn_status,
+            _ => panic!("expect a txn status from async-commit prewrite"),
+        };
+        let ctx = Context::default();
+        engine.write(&ctx, ret.to_be_write).unwrap();
+        Ok(txn_status)
+    }
+
+    #[test]
+    fn test_async_commit_prewrite_returns_min_commit_ts() {
+        let engine = TestEngineBuilder::new().build().unwrap();
+        let mut statistics = Statistics::default();
+        let k1 = Key::from_raw(b"k1");
+        let k2 = Key::from_raw(b"k2");
+        let status = async_commit_prewrite(
+            &engine,
+            &mut statistics,
+            vec![
+                Mutation::Put((k1.clone(), b"v1".to_vec())),
+                Mutation::Put((k2, b"v2".to_vec())),
+            ],
+            b"k1".to_vec(),
+            vec![b"k2".to_vec()],
+            10,
+        )
+        .unwrap();
+        // Prewrite reports the largest computed min_commit_ts and the secondary
+        // list so the client can commit without a second PD timestamp fetch.
+        match status {
+            TxnStatus::Uncommitted {
+                min_commit_ts,
+                secondaries,
+                ..
+            } => {
+                assert!(min_commit_ts > 10.into());
+                assert_eq!(secondaries, vec![b"k2".to_vec()]);
+            }
+            other => panic!("expect Uncommitted, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_one_pc_prewrite() {
+        let engine = TestEngineBuilder::new().build().unwrap();
+        let mut statistics = Statistics::default();
+        let key = Key::from_raw(b"k1");
+        let commit_ts = one_pc_prewrite(
+            &engine,
+            &mut statistics,
+            vec![Mutation::Put((key.clone(), b"v1".to_vec()))],
+            b"k1".to_vec(),
+            10,
+        )
+        .unwrap();
+        // The chosen commit_ts is returned and strictly greater than start_ts.
+        assert!(commit_ts > 10.into());
+
+        let ctx = Context::default();
+        let snap = engine.snapshot(&ctx).unwrap();
+        // The value is committed and visible without a separate `commit` call.
+        let write = snap
+            .get_cf(CF_WRITE, &key.clone().append_ts(commit_ts))
+            .unwrap();
+        assert!(write.is_some());
+        // No lock is left behind by the 1PC path.
+        let lock = snap.get_cf(engine::CF_LOCK, &key).unwrap();
+        assert!(lock.is_none());
+    }
+
+    #[test]
+    fn test_one_pc_prewrite_conflict_falls_back() {
+        let engine = TestEngineBuilder::new().build().unwrap();
+        let mut statistics = Statistics::default();
+        let raw = b"k1".to_vec();
+        let key = Key::from_raw(&raw);
+        // Commit a version newer than the later 1PC attempt's start_ts.
+        prewrite(
+            &engine,
+            &mut statistics,
+            vec![Mutation::Put((key.clone(), b"v0".to_vec()))],
+            raw.clone(),
+            10,
+        )
+        .unwrap();
+        commit(&engine, &mut statistics, vec![key.clone()], 10, 15).unwrap();
+
+        // A 1PC attempt at an older start_ts hits a write conflict and must return
+        // an error so the client retries with 2PC, never committing in one phase.
+        let ctx = Context::default();
+        let snap = engine.snapshot(&ctx).unwrap();
+        let mut options = Options::default();
+        options.try_one_pc = true;
+        let cmd = Command {
+            ctx,
+            kind: CommandKind::Prewrite {
+                mutations: vec![Mutation::Put((key.clone(), b"v1".to_vec()))],
+                primary: raw,
+                start_ts: TimeStamp::from(5),
+                options,
+            },
+        };
+        let m = DummyLockManager {};
+        let res = process_write_impl(cmd, snap, Some(m), &mut statistics);
+        assert!(
+            res.is_err(),
+            "a conflicting 1PC prewrite must return an error for 2PC fallback"
+        );
+
+        // The conflicting key stays unwritten and unlocked.
+        let snap = engine.snapshot(&Context::default()).unwrap();
+        assert!(snap.get_cf(engine::CF_LOCK, &key).unwrap().is_none());
+    }
 }